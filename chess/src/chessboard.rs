@@ -0,0 +1,900 @@
+use serde::{Deserialize, Serialize};
+
+use crate::piece::{Color, Piece};
+use crate::square::Square;
+use crate::{CastleType, ChessError, MoveType};
+
+/// Bit `1 << WK` etc. in [`ChessBoard::castling_rights`].
+const WK: u8 = 0;
+const WQ: u8 = 1;
+const BK: u8 = 2;
+const BQ: u8 = 3;
+
+/// Zobrist keys: 12 pieces * 64 squares, plus one side-to-move key, four
+/// castling-right keys and eight en-passant-file keys.
+const ZOBRIST_PIECE_SQUARE_KEYS: usize = 12 * 64;
+const ZOBRIST_SIDE_TO_MOVE_KEY: usize = ZOBRIST_PIECE_SQUARE_KEYS;
+const ZOBRIST_CASTLING_KEYS: usize = ZOBRIST_SIDE_TO_MOVE_KEY + 1;
+const ZOBRIST_EN_PASSANT_KEYS: usize = ZOBRIST_CASTLING_KEYS + 4;
+const ZOBRIST_KEY_COUNT: usize = ZOBRIST_EN_PASSANT_KEYS + 8;
+
+/// Deterministic splitmix64-derived keys, computed once at compile time so
+/// every validator produces the same hash for the same position.
+const fn generate_zobrist_keys() -> [u64; ZOBRIST_KEY_COUNT] {
+    let mut keys = [0u64; ZOBRIST_KEY_COUNT];
+    let mut seed: u64 = 0x5EED_C0FF_EE15_2026;
+    let mut i = 0;
+    while i < ZOBRIST_KEY_COUNT {
+        seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        keys[i] = z;
+        i += 1;
+    }
+    keys
+}
+
+const ZOBRIST_KEYS: [u64; ZOBRIST_KEY_COUNT] = generate_zobrist_keys();
+
+/// Bitboard representation of a chess position. Each bitboard is indexed
+/// little-endian rank-file (see [`Square`]), so `square as usize` is always
+/// the bit position to test/set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChessBoard {
+    pub pieces: [u64; 12],
+    /// Target square of a pawn that just made a double push, or 0 if none.
+    pub en_passant: u64,
+    /// Bit `i` set means the right named by `WK`/`WQ`/`BK`/`BQ` is still held.
+    pub castling_rights: u8,
+}
+
+impl Default for ChessBoard {
+    fn default() -> Self {
+        ChessBoard::starting_position()
+    }
+}
+
+impl ChessBoard {
+    pub fn starting_position() -> ChessBoard {
+        let mut pieces = [0u64; 12];
+        pieces[Piece::WhitePawn.index()] = 0x0000_0000_0000_FF00;
+        pieces[Piece::WhiteRook.index()] = (1 << Square::A1 as usize) | (1 << Square::H1 as usize);
+        pieces[Piece::WhiteKnight.index()] = (1 << Square::B1 as usize) | (1 << Square::G1 as usize);
+        pieces[Piece::WhiteBishop.index()] = (1 << Square::C1 as usize) | (1 << Square::F1 as usize);
+        pieces[Piece::WhiteQueen.index()] = 1 << Square::D1 as usize;
+        pieces[Piece::WhiteKing.index()] = 1 << Square::E1 as usize;
+        pieces[Piece::BlackPawn.index()] = 0x00FF_0000_0000_0000;
+        pieces[Piece::BlackRook.index()] = (1 << Square::A8 as usize) | (1 << Square::H8 as usize);
+        pieces[Piece::BlackKnight.index()] = (1 << Square::B8 as usize) | (1 << Square::G8 as usize);
+        pieces[Piece::BlackBishop.index()] = (1 << Square::C8 as usize) | (1 << Square::F8 as usize);
+        pieces[Piece::BlackQueen.index()] = 1 << Square::D8 as usize;
+        pieces[Piece::BlackKing.index()] = 1 << Square::E8 as usize;
+
+        ChessBoard {
+            pieces,
+            en_passant: 0,
+            castling_rights: (1 << WK) | (1 << WQ) | (1 << BK) | (1 << BQ),
+        }
+    }
+
+    pub fn occupancy(&self) -> u64 {
+        self.pieces.iter().fold(0, |acc, bb| acc | bb)
+    }
+
+    pub fn color_occupancy(&self, color: Color) -> u64 {
+        Piece::ALL
+            .iter()
+            .filter(|p| p.color() == color)
+            .fold(0, |acc, p| acc | self.pieces[p.index()])
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        let bit = 1u64 << square.index();
+        Piece::ALL
+            .into_iter()
+            .find(|p| self.pieces[p.index()] & bit != 0)
+    }
+
+    pub fn remove_piece(&mut self, square: Square, piece: Piece) {
+        self.pieces[piece.index()] &= !(1u64 << square.index());
+    }
+
+    pub fn place_piece(&mut self, square: Square, piece: Piece) {
+        self.pieces[piece.index()] |= 1u64 << square.index();
+    }
+
+    pub fn has_castling_right(&self, color: Color, side: CastleType) -> bool {
+        let bit = match (color, side) {
+            (Color::White, CastleType::KingSide) => WK,
+            (Color::White, CastleType::QueenSide) => WQ,
+            (Color::Black, CastleType::KingSide) => BK,
+            (Color::Black, CastleType::QueenSide) => BQ,
+        };
+        self.castling_rights & (1 << bit) != 0
+    }
+
+    fn clear_castling_right(&mut self, color: Color, side: CastleType) {
+        let bit = match (color, side) {
+            (Color::White, CastleType::KingSide) => WK,
+            (Color::White, CastleType::QueenSide) => WQ,
+            (Color::Black, CastleType::KingSide) => BK,
+            (Color::Black, CastleType::QueenSide) => BQ,
+        };
+        self.castling_rights &= !(1 << bit);
+    }
+
+    /// Parses the two-letter contract notation used by `contract.rs`
+    /// (e.g. `"wP"`, `"bK"`) into a [`Piece`].
+    pub fn get_piece(code: &str) -> Option<Piece> {
+        code.parse().ok()
+    }
+
+    /// Builds the `"<from>x<to>"` move string used for captures.
+    pub fn create_capture_string(from: &str, to: &str) -> String {
+        format!("{}x{}", from, to)
+    }
+
+    /// Applies a pseudo-legal move to the board, updating castling rights
+    /// and the en-passant target. Does not flip the active color or touch
+    /// game-over state; that is the caller's (`Game`) responsibility.
+    pub fn apply_move(
+        &mut self,
+        from: Square,
+        to: Square,
+        piece: Piece,
+        move_type: MoveType,
+    ) -> Result<(), ChessError> {
+        if self.pieces[piece.index()] & (1u64 << from.index()) == 0 {
+            return Err(ChessError::InvalidMove);
+        }
+
+        self.remove_piece(from, piece);
+
+        match move_type {
+            MoveType::Move => {
+                self.place_piece(to, piece);
+            }
+            MoveType::Capture(captured) => {
+                self.remove_piece(to, captured);
+                self.place_piece(to, piece);
+            }
+            MoveType::EnPassant => {
+                let captured_square = Square::from_index(match piece.color() {
+                    Color::White => to.index() - 8,
+                    Color::Black => to.index() + 8,
+                })
+                .ok_or(ChessError::InvalidMove)?;
+                let captured = match piece.color() {
+                    Color::White => Piece::BlackPawn,
+                    Color::Black => Piece::WhitePawn,
+                };
+                self.remove_piece(captured_square, captured);
+                self.place_piece(to, piece);
+            }
+            MoveType::Promotion(promoted) => {
+                if promoted.color() != piece.color() {
+                    return Err(ChessError::InvalidPromotion);
+                }
+                if let Some(captured) = self.piece_at(to) {
+                    self.remove_piece(to, captured);
+                }
+                self.place_piece(to, promoted);
+            }
+            MoveType::Castle(side) => {
+                self.place_piece(to, piece);
+                let rank = from.rank();
+                let (rook_from, rook_to) = match side {
+                    CastleType::KingSide => (
+                        Square::from_file_rank(8, rank).ok_or(ChessError::InvalidMove)?,
+                        Square::from_file_rank(6, rank).ok_or(ChessError::InvalidMove)?,
+                    ),
+                    CastleType::QueenSide => (
+                        Square::from_file_rank(1, rank).ok_or(ChessError::InvalidMove)?,
+                        Square::from_file_rank(4, rank).ok_or(ChessError::InvalidMove)?,
+                    ),
+                };
+                let rook = match piece.color() {
+                    Color::White => Piece::WhiteRook,
+                    Color::Black => Piece::BlackRook,
+                };
+                self.remove_piece(rook_from, rook);
+                self.place_piece(rook_to, rook);
+            }
+        }
+
+        self.update_castling_rights(from, to, piece);
+        self.update_en_passant(from, to, piece);
+
+        Ok(())
+    }
+
+    fn update_castling_rights(&mut self, from: Square, to: Square, piece: Piece) {
+        match piece {
+            Piece::WhiteKing => {
+                self.clear_castling_right(Color::White, CastleType::KingSide);
+                self.clear_castling_right(Color::White, CastleType::QueenSide);
+            }
+            Piece::BlackKing => {
+                self.clear_castling_right(Color::Black, CastleType::KingSide);
+                self.clear_castling_right(Color::Black, CastleType::QueenSide);
+            }
+            _ => {}
+        }
+        for square in [from, to] {
+            match square {
+                Square::A1 => self.clear_castling_right(Color::White, CastleType::QueenSide),
+                Square::H1 => self.clear_castling_right(Color::White, CastleType::KingSide),
+                Square::A8 => self.clear_castling_right(Color::Black, CastleType::QueenSide),
+                Square::H8 => self.clear_castling_right(Color::Black, CastleType::KingSide),
+                _ => {}
+            }
+        }
+    }
+
+    fn update_en_passant(&mut self, from: Square, to: Square, piece: Piece) {
+        self.en_passant = 0;
+        if piece.is_pawn() && from.rank().abs_diff(to.rank()) == 2 {
+            let target = match piece.color() {
+                Color::White => from.index() + 8,
+                Color::Black => from.index() - 8,
+            };
+            self.en_passant = 1u64 << target;
+        }
+    }
+
+    /// Serializes the piece placement field of a FEN record.
+    pub fn to_fen_placement(&self) -> String {
+        let mut ranks = Vec::with_capacity(8);
+        for rank in (1..=8).rev() {
+            let mut row = String::new();
+            let mut empty = 0u8;
+            for file in 1..=8 {
+                let square = Square::from_file_rank(file, rank).expect("in range");
+                match self.piece_at(square) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            row.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        row.push(piece.fen_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                row.push_str(&empty.to_string());
+            }
+            ranks.push(row);
+        }
+        ranks.join("/")
+    }
+
+    /// Squares attacked by a knight standing on `square`.
+    pub fn knight_attacks(square: Square) -> u64 {
+        let file = square.file() as i8;
+        let rank = square.rank() as i8;
+        const OFFSETS: [(i8, i8); 8] = [
+            (1, 2), (2, 1), (2, -1), (1, -2),
+            (-1, -2), (-2, -1), (-2, 1), (-1, 2),
+        ];
+        OFFSETS.iter().fold(0u64, |acc, (df, dr)| {
+            match Square::from_file_rank((file + df) as u8, (rank + dr) as u8) {
+                Some(s) if (1..=8).contains(&(file + df)) && (1..=8).contains(&(rank + dr)) => {
+                    acc | (1u64 << s.index())
+                }
+                _ => acc,
+            }
+        })
+    }
+
+    /// Squares attacked by a king standing on `square`.
+    pub fn king_attacks(square: Square) -> u64 {
+        let file = square.file() as i8;
+        let rank = square.rank() as i8;
+        let mut attacks = 0u64;
+        for df in -1..=1i8 {
+            for dr in -1..=1i8 {
+                if df == 0 && dr == 0 {
+                    continue;
+                }
+                let (f, r) = (file + df, rank + dr);
+                if (1..=8).contains(&f) && (1..=8).contains(&r) {
+                    attacks |= 1u64 << Square::from_file_rank(f as u8, r as u8).unwrap().index();
+                }
+            }
+        }
+        attacks
+    }
+
+    /// Squares a pawn of `color` standing on `square` attacks (captures to).
+    pub fn pawn_attacks(square: Square, color: Color) -> u64 {
+        let file = square.file() as i8;
+        let rank = square.rank() as i8;
+        let dr = match color {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+        [-1i8, 1].iter().fold(0u64, |acc, df| {
+            let (f, r) = (file + df, rank + dr);
+            if (1..=8).contains(&f) && (1..=8).contains(&r) {
+                acc | (1u64 << Square::from_file_rank(f as u8, r as u8).unwrap().index())
+            } else {
+                acc
+            }
+        })
+    }
+
+    const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+    const ROOK_DIRS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+    fn sliding_attacks(square: Square, occupancy: u64, dirs: &[(i8, i8)]) -> u64 {
+        let mut attacks = 0u64;
+        for (df, dr) in dirs {
+            let (mut f, mut r) = (square.file() as i8, square.rank() as i8);
+            loop {
+                f += df;
+                r += dr;
+                if !(1..=8).contains(&f) || !(1..=8).contains(&r) {
+                    break;
+                }
+                let s = Square::from_file_rank(f as u8, r as u8).unwrap();
+                attacks |= 1u64 << s.index();
+                if occupancy & (1u64 << s.index()) != 0 {
+                    break;
+                }
+            }
+        }
+        attacks
+    }
+
+    pub fn bishop_attacks(square: Square, occupancy: u64) -> u64 {
+        Self::sliding_attacks(square, occupancy, &Self::BISHOP_DIRS)
+    }
+
+    pub fn rook_attacks(square: Square, occupancy: u64) -> u64 {
+        Self::sliding_attacks(square, occupancy, &Self::ROOK_DIRS)
+    }
+
+    pub fn queen_attacks(square: Square, occupancy: u64) -> u64 {
+        Self::bishop_attacks(square, occupancy) | Self::rook_attacks(square, occupancy)
+    }
+
+    /// Whether `square` is attacked by any piece of `by_color`.
+    pub fn is_attacked(&self, square: Square, by_color: Color) -> bool {
+        let occupancy = self.occupancy();
+
+        let knight = match by_color {
+            Color::White => Piece::WhiteKnight,
+            Color::Black => Piece::BlackKnight,
+        };
+        if Self::knight_attacks(square) & self.pieces[knight.index()] != 0 {
+            return true;
+        }
+
+        let king = match by_color {
+            Color::White => Piece::WhiteKing,
+            Color::Black => Piece::BlackKing,
+        };
+        if Self::king_attacks(square) & self.pieces[king.index()] != 0 {
+            return true;
+        }
+
+        // A pawn of `by_color` attacks `square` iff it stands on one of the
+        // squares that a pawn of the opposite color on `square` would attack.
+        let pawn = match by_color {
+            Color::White => Piece::WhitePawn,
+            Color::Black => Piece::BlackPawn,
+        };
+        if Self::pawn_attacks(square, by_color.opponent()) & self.pieces[pawn.index()] != 0 {
+            return true;
+        }
+
+        let bishop = match by_color {
+            Color::White => Piece::WhiteBishop,
+            Color::Black => Piece::BlackBishop,
+        };
+        let rook = match by_color {
+            Color::White => Piece::WhiteRook,
+            Color::Black => Piece::BlackRook,
+        };
+        let queen = match by_color {
+            Color::White => Piece::WhiteQueen,
+            Color::Black => Piece::BlackQueen,
+        };
+        if Self::bishop_attacks(square, occupancy) & (self.pieces[bishop.index()] | self.pieces[queen.index()]) != 0 {
+            return true;
+        }
+        if Self::rook_attacks(square, occupancy) & (self.pieces[rook.index()] | self.pieces[queen.index()]) != 0 {
+            return true;
+        }
+
+        false
+    }
+
+    pub fn king_square(&self, color: Color) -> Option<Square> {
+        let king = match color {
+            Color::White => Piece::WhiteKing,
+            Color::Black => Piece::BlackKing,
+        };
+        let bb = self.pieces[king.index()];
+        if bb == 0 {
+            None
+        } else {
+            Square::from_index(bb.trailing_zeros() as u8)
+        }
+    }
+
+    pub fn is_in_check(&self, color: Color) -> bool {
+        match self.king_square(color) {
+            Some(square) => self.is_attacked(square, color.opponent()),
+            None => false,
+        }
+    }
+
+    /// Pseudo-legal destinations for the piece on `square`, ignoring
+    /// whether the move would leave the mover's own king in check.
+    fn pseudo_destinations(&self, square: Square, piece: Piece) -> u64 {
+        let occupancy = self.occupancy();
+        let own = self.color_occupancy(piece.color());
+        match piece {
+            Piece::WhiteKnight | Piece::BlackKnight => Self::knight_attacks(square) & !own,
+            Piece::WhiteKing | Piece::BlackKing => Self::king_attacks(square) & !own,
+            Piece::WhiteBishop | Piece::BlackBishop => Self::bishop_attacks(square, occupancy) & !own,
+            Piece::WhiteRook | Piece::BlackRook => Self::rook_attacks(square, occupancy) & !own,
+            Piece::WhiteQueen | Piece::BlackQueen => Self::queen_attacks(square, occupancy) & !own,
+            Piece::WhitePawn | Piece::BlackPawn => {
+                self.pawn_destinations(square, piece.color(), occupancy)
+            }
+        }
+    }
+
+    fn pawn_destinations(&self, square: Square, color: Color, occupancy: u64) -> u64 {
+        let mut dests = 0u64;
+        let dir: i8 = if color == Color::White { 1 } else { -1 };
+        let start_rank: i8 = if color == Color::White { 2 } else { 7 };
+        let file = square.file() as i8;
+        let rank = square.rank() as i8;
+
+        if let Some(one) = Square::from_file_rank(file as u8, (rank + dir) as u8) {
+            if occupancy & (1u64 << one.index()) == 0 {
+                dests |= 1u64 << one.index();
+                if rank == start_rank {
+                    if let Some(two) = Square::from_file_rank(file as u8, (rank + 2 * dir) as u8) {
+                        if occupancy & (1u64 << two.index()) == 0 {
+                            dests |= 1u64 << two.index();
+                        }
+                    }
+                }
+            }
+        }
+
+        let capturable = self.color_occupancy(color.opponent()) | self.en_passant;
+        dests |= Self::pawn_attacks(square, color) & capturable;
+        dests
+    }
+
+    /// Whether moving `piece` from `from` to `to` would leave `color`'s own
+    /// king in check. Used to filter pseudo-legal moves down to legal ones.
+    fn leaves_king_in_check(&self, from: Square, to: Square, piece: Piece, color: Color) -> bool {
+        let mut sim = self.clone();
+        if let Some(captured) = sim.piece_at(to) {
+            sim.remove_piece(to, captured);
+        }
+        sim.remove_piece(from, piece);
+        sim.place_piece(to, piece);
+        sim.is_in_check(color)
+    }
+
+    /// Legal destination squares for the piece standing on `from`, empty if
+    /// there is no piece of `color` there. Does not include castling, which
+    /// the contract still derives and applies as a dedicated `MoveType`.
+    pub fn legal_destinations(&self, from: Square, color: Color) -> Vec<Square> {
+        let piece = match self.piece_at(from) {
+            Some(p) if p.color() == color => p,
+            _ => return Vec::new(),
+        };
+        let mut dests = self.pseudo_destinations(from, piece);
+        let mut out = Vec::new();
+        while dests != 0 {
+            let to_index = dests.trailing_zeros() as u8;
+            dests &= dests - 1;
+            let to = Square::from_index(to_index).expect("valid bit index");
+            if !self.leaves_king_in_check(from, to, piece, color) {
+                out.push(to);
+            }
+        }
+        out
+    }
+
+    pub fn has_any_legal_move(&self, color: Color) -> bool {
+        let has_basic_move = Piece::ALL
+            .into_iter()
+            .filter(|p| p.color() == color)
+            .any(|piece| {
+                let mut bb = self.pieces[piece.index()];
+                while bb != 0 {
+                    let from_index = bb.trailing_zeros() as u8;
+                    bb &= bb - 1;
+                    let from = Square::from_index(from_index).expect("valid bit index");
+                    if !self.legal_destinations(from, color).is_empty() {
+                        return true;
+                    }
+                }
+                false
+            });
+        has_basic_move || !self.legal_castle_moves(color).is_empty()
+    }
+
+    /// Castling moves currently available to `color`: both the king's and
+    /// rook's home squares must be untouched (`castling_rights`), the
+    /// squares between them empty, and the king may not start, pass
+    /// through, or land on an attacked square.
+    pub fn legal_castle_moves(&self, color: Color) -> Vec<(Square, Square, CastleType)> {
+        let mut moves = Vec::new();
+        let rank = match color {
+            Color::White => 1,
+            Color::Black => 8,
+        };
+        let king_from = match Square::from_file_rank(5, rank) {
+            Some(s) => s,
+            None => return moves,
+        };
+        let opponent = color.opponent();
+        if self.is_attacked(king_from, opponent) {
+            return moves;
+        }
+        let occupancy = self.occupancy();
+
+        if self.has_castling_right(color, CastleType::KingSide) {
+            let f = Square::from_file_rank(6, rank).expect("in range");
+            let g = Square::from_file_rank(7, rank).expect("in range");
+            let empty = occupancy & ((1u64 << f.index()) | (1u64 << g.index())) == 0;
+            if empty && !self.is_attacked(f, opponent) && !self.is_attacked(g, opponent) {
+                moves.push((king_from, g, CastleType::KingSide));
+            }
+        }
+
+        if self.has_castling_right(color, CastleType::QueenSide) {
+            let d = Square::from_file_rank(4, rank).expect("in range");
+            let c = Square::from_file_rank(3, rank).expect("in range");
+            let b = Square::from_file_rank(2, rank).expect("in range");
+            let empty =
+                occupancy & ((1u64 << d.index()) | (1u64 << c.index()) | (1u64 << b.index())) == 0;
+            if empty && !self.is_attacked(d, opponent) && !self.is_attacked(c, opponent) {
+                moves.push((king_from, c, CastleType::QueenSide));
+            }
+        }
+
+        moves
+    }
+
+    /// Derives the `MoveType` for a submitted `(from, to)` move instead of
+    /// trusting the client's classification. `promoted_piece` must be
+    /// `Some` iff the move is a pawn reaching the back rank.
+    pub fn classify_move(
+        &self,
+        from: Square,
+        to: Square,
+        piece: Piece,
+        promoted_piece: Option<Piece>,
+    ) -> Result<MoveType, ChessError> {
+        if piece.is_king() {
+            let file_diff = to.file() as i8 - from.file() as i8;
+            if file_diff == 2 {
+                return Ok(MoveType::Castle(CastleType::KingSide));
+            }
+            if file_diff == -2 {
+                return Ok(MoveType::Castle(CastleType::QueenSide));
+            }
+        }
+
+        if piece.is_pawn() && self.en_passant & (1u64 << to.index()) != 0 && self.piece_at(to).is_none()
+        {
+            return Ok(MoveType::EnPassant);
+        }
+
+        let back_rank = match piece.color() {
+            Color::White => 8,
+            Color::Black => 1,
+        };
+        if piece.is_pawn() && to.rank() == back_rank {
+            let promoted = promoted_piece.ok_or(ChessError::InvalidPromotion)?;
+            if promoted.color() != piece.color() || promoted.is_pawn() || promoted.is_king() {
+                return Err(ChessError::InvalidPromotion);
+            }
+            return Ok(MoveType::Promotion(promoted));
+        }
+
+        match self.piece_at(to) {
+            Some(captured) => Ok(MoveType::Capture(captured)),
+            None => Ok(MoveType::Move),
+        }
+    }
+
+    /// Whether `(from, to)` is a legal move for `color`, covering both the
+    /// basic move generator and castling.
+    pub fn is_legal_move(&self, from: Square, to: Square, color: Color) -> bool {
+        self.legal_destinations(from, color).contains(&to)
+            || self
+                .legal_castle_moves(color)
+                .iter()
+                .any(|(f, t, _)| *f == from && *t == to)
+    }
+
+    /// Zobrist hash of the position, including whose move it is. Only
+    /// positions with the same castling and en-passant rights hash equal,
+    /// which is exactly what the threefold-repetition rule requires.
+    pub fn zobrist_hash(&self, active: Color) -> u64 {
+        let mut hash = 0u64;
+
+        for piece in Piece::ALL {
+            let mut bb = self.pieces[piece.index()];
+            while bb != 0 {
+                let square = bb.trailing_zeros() as usize;
+                bb &= bb - 1;
+                hash ^= ZOBRIST_KEYS[piece.index() * 64 + square];
+            }
+        }
+
+        if active == Color::Black {
+            hash ^= ZOBRIST_KEYS[ZOBRIST_SIDE_TO_MOVE_KEY];
+        }
+
+        for bit in [WK, WQ, BK, BQ] {
+            if self.castling_rights & (1 << bit) != 0 {
+                hash ^= ZOBRIST_KEYS[ZOBRIST_CASTLING_KEYS + bit as usize];
+            }
+        }
+
+        if self.en_passant != 0 {
+            let file = Square::from_index(self.en_passant.trailing_zeros() as u8)
+                .expect("valid en-passant bit")
+                .file() as usize
+                - 1;
+            hash ^= ZOBRIST_KEYS[ZOBRIST_EN_PASSANT_KEYS + file];
+        }
+
+        hash
+    }
+
+    /// Parses the piece placement field of a FEN record.
+    pub fn from_fen_placement(placement: &str) -> Result<ChessBoard, ChessError> {
+        let rows: Vec<&str> = placement.split('/').collect();
+        if rows.len() != 8 {
+            return Err(ChessError::InvalidFen(format!(
+                "expected 8 ranks, got {}",
+                rows.len()
+            )));
+        }
+
+        let mut board = ChessBoard {
+            pieces: [0u64; 12],
+            en_passant: 0,
+            castling_rights: 0,
+        };
+
+        for (i, row) in rows.iter().enumerate() {
+            let rank = 8 - i as u8;
+            let mut file = 1u8;
+            for c in row.chars() {
+                if let Some(skip) = c.to_digit(10) {
+                    file += skip as u8;
+                    continue;
+                }
+                let piece = Piece::from_fen_char(c)
+                    .ok_or_else(|| ChessError::InvalidFen(format!("bad piece char '{c}'")))?;
+                let square = Square::from_file_rank(file, rank)
+                    .ok_or_else(|| ChessError::InvalidFen("rank overflow".to_string()))?;
+                board.place_piece(square, piece);
+                file += 1;
+            }
+            if file != 9 {
+                return Err(ChessError::InvalidFen(format!(
+                    "rank {rank} does not sum to 8 files"
+                )));
+            }
+        }
+
+        Ok(board)
+    }
+
+    #[cfg(test)]
+    fn empty() -> ChessBoard {
+        ChessBoard {
+            pieces: [0u64; 12],
+            en_passant: 0,
+            castling_rights: 0,
+        }
+    }
+
+    /// Rejects a position that could never be reached by legal play: wrong
+    /// king counts, kings standing next to each other, pawns on the back
+    /// ranks, castling rights that don't match an unmoved king and rook, an
+    /// en-passant target that isn't actually behind a just-pushed pawn, or
+    /// the side not to move already being in check.
+    pub fn validate(&self, active: Color) -> Result<(), ChessError> {
+        for color in [Color::White, Color::Black] {
+            let king = match color {
+                Color::White => Piece::WhiteKing,
+                Color::Black => Piece::BlackKing,
+            };
+            if self.pieces[king.index()].count_ones() != 1 {
+                return Err(ChessError::InvalidPosition(format!(
+                    "{color:?} must have exactly one king"
+                )));
+            }
+        }
+
+        let white_king = self.king_square(Color::White).expect("checked above");
+        let black_king = self.king_square(Color::Black).expect("checked above");
+        if Self::king_attacks(white_king) & (1u64 << black_king.index()) != 0 {
+            return Err(ChessError::InvalidPosition(
+                "kings cannot stand on adjacent squares".to_string(),
+            ));
+        }
+
+        for file in 1..=8u8 {
+            for rank in [1u8, 8u8] {
+                let square = Square::from_file_rank(file, rank).expect("in range");
+                if matches!(self.piece_at(square), Some(p) if p.is_pawn()) {
+                    return Err(ChessError::InvalidPosition(format!(
+                        "pawn cannot stand on {square}"
+                    )));
+                }
+            }
+        }
+
+        for (color, side, king_square, rook_square) in [
+            (Color::White, CastleType::KingSide, Square::E1, Square::H1),
+            (Color::White, CastleType::QueenSide, Square::E1, Square::A1),
+            (Color::Black, CastleType::KingSide, Square::E8, Square::H8),
+            (Color::Black, CastleType::QueenSide, Square::E8, Square::A8),
+        ] {
+            if !self.has_castling_right(color, side) {
+                continue;
+            }
+            let king_piece = match color {
+                Color::White => Piece::WhiteKing,
+                Color::Black => Piece::BlackKing,
+            };
+            let rook_piece = match color {
+                Color::White => Piece::WhiteRook,
+                Color::Black => Piece::BlackRook,
+            };
+            if self.piece_at(king_square) != Some(king_piece)
+                || self.piece_at(rook_square) != Some(rook_piece)
+            {
+                return Err(ChessError::InvalidPosition(format!(
+                    "{color:?} cannot have {side:?} castling rights without an unmoved king and rook"
+                )));
+            }
+        }
+
+        if self.en_passant != 0 {
+            let ep_square = Square::from_index(self.en_passant.trailing_zeros() as u8)
+                .ok_or_else(|| ChessError::InvalidPosition("invalid en-passant bit".to_string()))?;
+            let rank = ep_square.rank();
+            if rank != 3 && rank != 6 {
+                return Err(ChessError::InvalidPosition(format!(
+                    "en-passant target {ep_square} must be on rank 3 or 6"
+                )));
+            }
+            if self.piece_at(ep_square).is_some() {
+                return Err(ChessError::InvalidPosition(format!(
+                    "en-passant target {ep_square} must be empty"
+                )));
+            }
+
+            let (pawn_color, pawn_rank) = if rank == 3 {
+                (Color::White, 4)
+            } else {
+                (Color::Black, 5)
+            };
+            let pawn_square = Square::from_file_rank(ep_square.file(), pawn_rank)
+                .ok_or_else(|| ChessError::InvalidPosition("en-passant target out of range".to_string()))?;
+            let expected_pawn = match pawn_color {
+                Color::White => Piece::WhitePawn,
+                Color::Black => Piece::BlackPawn,
+            };
+            if self.piece_at(pawn_square) != Some(expected_pawn) {
+                return Err(ChessError::InvalidPosition(format!(
+                    "en-passant target {ep_square} is not behind a {pawn_color:?} pawn"
+                )));
+            }
+        }
+
+        if self.is_in_check(active.opponent()) {
+            return Err(ChessError::InvalidPosition(
+                "the side not to move cannot already be in check".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_move_detects_en_passant() {
+        let mut board = ChessBoard::starting_position();
+        board.en_passant = 1u64 << Square::E6.index();
+        let move_type = board
+            .classify_move(Square::D5, Square::E6, Piece::WhitePawn, None)
+            .unwrap();
+        assert_eq!(move_type, MoveType::EnPassant);
+    }
+
+    #[test]
+    fn classify_move_requires_promoted_piece_on_back_rank() {
+        let board = ChessBoard::starting_position();
+        let err = board
+            .classify_move(Square::A7, Square::A8, Piece::WhitePawn, None)
+            .unwrap_err();
+        assert!(matches!(err, ChessError::InvalidPromotion));
+    }
+
+    #[test]
+    fn classify_move_reports_promotion_even_when_capturing() {
+        let mut board = ChessBoard::empty();
+        board.place_piece(Square::A7, Piece::WhitePawn);
+        board.place_piece(Square::B8, Piece::BlackRook);
+
+        let move_type = board
+            .classify_move(
+                Square::A7,
+                Square::B8,
+                Piece::WhitePawn,
+                Some(Piece::WhiteQueen),
+            )
+            .unwrap();
+
+        assert_eq!(move_type, MoveType::Promotion(Piece::WhiteQueen));
+        assert_eq!(board.piece_at(Square::B8), Some(Piece::BlackRook));
+    }
+
+    #[test]
+    fn legal_destinations_excludes_moves_that_leave_king_in_check() {
+        let mut board = ChessBoard::empty();
+        board.place_piece(Square::E1, Piece::WhiteKing);
+        board.place_piece(Square::E2, Piece::WhiteBishop);
+        board.place_piece(Square::E8, Piece::BlackRook);
+        board.place_piece(Square::H8, Piece::BlackKing);
+
+        // The bishop is pinned along the e-file and cannot step off it.
+        assert!(board.legal_destinations(Square::E2, Color::White).is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_missing_king() {
+        let mut board = ChessBoard::empty();
+        board.place_piece(Square::E1, Piece::WhiteKing);
+        assert!(matches!(
+            board.validate(Color::White),
+            Err(ChessError::InvalidPosition(_))
+        ));
+    }
+
+    #[test]
+    fn validate_rejects_side_not_to_move_already_in_check() {
+        let mut board = ChessBoard::empty();
+        board.place_piece(Square::E1, Piece::WhiteKing);
+        board.place_piece(Square::E2, Piece::WhiteRook);
+        board.place_piece(Square::E8, Piece::BlackKing);
+
+        // It is White to move, so Black cannot already be in check.
+        assert!(matches!(
+            board.validate(Color::White),
+            Err(ChessError::InvalidPosition(_))
+        ));
+    }
+
+    #[test]
+    fn validate_accepts_starting_position() {
+        assert!(ChessBoard::starting_position().validate(Color::White).is_ok());
+    }
+}
@@ -0,0 +1,124 @@
+use linera_sdk::base::{TimeDelta, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::piece::Color;
+use crate::{InstantiationArgument, TimeIncrement};
+
+/// Enforces a per-move deadline (each player has `block_delay` to submit
+/// their move after the previous one lands, or the operation is rejected by
+/// `ContractRuntime::assert_before`) and a per-side chess clock that ends
+/// the game on flag-fall.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Clock {
+    pub block_delay: TimeDelta,
+    pub last_move: Timestamp,
+    /// Time left on each side's clock, indexed by `Color as usize`.
+    pub remaining: [TimeDelta; 2],
+    pub increment: TimeIncrement,
+}
+
+impl Clock {
+    pub fn new(now: Timestamp, argument: &InstantiationArgument) -> Clock {
+        Clock {
+            block_delay: argument.block_delay,
+            last_move: now,
+            remaining: [argument.initial_time, argument.initial_time],
+            increment: argument.increment,
+        }
+    }
+
+    /// The time actually charged to `active`'s clock for a move made at
+    /// `block_time`: the elapsed wall time since `last_move`, minus any
+    /// free delay period.
+    fn charged_time(&self, block_time: Timestamp) -> TimeDelta {
+        let elapsed = block_time.delta_since(self.last_move);
+        match self.increment {
+            TimeIncrement::Delay(delay) => elapsed.saturating_sub(delay),
+            TimeIncrement::None | TimeIncrement::Fischer(_) => elapsed,
+        }
+    }
+
+    /// Whether `active`'s clock has already run out as of `block_time`,
+    /// before their move is even applied.
+    pub fn has_flagged(&self, block_time: Timestamp, active: Color) -> bool {
+        self.charged_time(block_time) >= self.remaining[active as usize]
+    }
+
+    /// Charges the elapsed time to `active`'s clock and applies any Fischer
+    /// increment. Callers must check [`Clock::has_flagged`] first; this does
+    /// not itself detect flag-fall.
+    pub fn make_move(&mut self, block_time: Timestamp, active: Color) {
+        let charged = self.charged_time(block_time);
+        self.remaining[active as usize] = self.remaining[active as usize].saturating_sub(charged);
+        if let TimeIncrement::Fischer(increment) = self.increment {
+            self.remaining[active as usize] =
+                self.remaining[active as usize].saturating_add(increment);
+        }
+        self.last_move = block_time;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(increment: TimeIncrement) -> Clock {
+        Clock {
+            block_delay: TimeDelta::from_micros(0),
+            last_move: Timestamp::from(1_000),
+            remaining: [TimeDelta::from_micros(10_000), TimeDelta::from_micros(10_000)],
+            increment,
+        }
+    }
+
+    #[test]
+    fn charged_time_is_the_full_elapsed_time_without_a_delay() {
+        let clock = clock(TimeIncrement::None);
+        let charged = clock.charged_time(Timestamp::from(1_000 + 4_000));
+        assert_eq!(charged, TimeDelta::from_micros(4_000));
+    }
+
+    #[test]
+    fn charged_time_with_delay_is_free_up_to_the_delay_amount() {
+        let clock = clock(TimeIncrement::Delay(TimeDelta::from_micros(2_000)));
+
+        let within_delay = clock.charged_time(Timestamp::from(1_000 + 1_500));
+        assert_eq!(within_delay, TimeDelta::from_micros(0));
+
+        let beyond_delay = clock.charged_time(Timestamp::from(1_000 + 5_000));
+        assert_eq!(beyond_delay, TimeDelta::from_micros(3_000));
+    }
+
+    #[test]
+    fn has_flagged_is_true_once_charged_time_reaches_remaining() {
+        let clock = clock(TimeIncrement::None);
+        assert!(!clock.has_flagged(Timestamp::from(1_000 + 9_999), Color::White));
+        assert!(clock.has_flagged(Timestamp::from(1_000 + 10_000), Color::White));
+    }
+
+    #[test]
+    fn make_move_deducts_charged_time_and_advances_last_move() {
+        let mut clock = clock(TimeIncrement::None);
+        clock.make_move(Timestamp::from(1_000 + 3_000), Color::White);
+
+        assert_eq!(clock.remaining[Color::White as usize], TimeDelta::from_micros(7_000));
+        assert_eq!(clock.remaining[Color::Black as usize], TimeDelta::from_micros(10_000));
+        assert_eq!(clock.last_move, Timestamp::from(4_000));
+    }
+
+    #[test]
+    fn make_move_with_fischer_increment_credits_time_back() {
+        let mut clock = clock(TimeIncrement::Fischer(TimeDelta::from_micros(2_000)));
+        clock.make_move(Timestamp::from(1_000 + 3_000), Color::White);
+
+        assert_eq!(clock.remaining[Color::White as usize], TimeDelta::from_micros(9_000));
+    }
+
+    #[test]
+    fn make_move_saturates_instead_of_underflowing_when_overdue() {
+        let mut clock = clock(TimeIncrement::None);
+        clock.make_move(Timestamp::from(1_000 + 50_000), Color::White);
+
+        assert_eq!(clock.remaining[Color::White as usize], TimeDelta::from_micros(0));
+    }
+}
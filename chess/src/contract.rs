@@ -10,8 +10,7 @@ use chess::{
     chessboard::ChessBoard,
     piece::{Color, Piece},
     square::Square,
-    CastleType, ChessError, ChessResponse, Clock, Game, GameState, InstantiationArgument, MoveType,
-    Operation,
+    ChessError, ChessResponse, Clock, Game, GameState, InstantiationArgument, MoveType, Operation,
 };
 use linera_sdk::{
     base::{Owner, TimeDelta, WithContractAbi},
@@ -74,6 +73,9 @@ impl Contract for ChessContract {
                     }
                     let game = Game::new();
                     // let game = Game::with_fen("8/7P/7P/8/8/8/8/7r w - - 0 1");
+                    if let Err(e) = game.board.validate(game.active) {
+                        return ChessResponse::Err(e);
+                    }
                     self.state.add_player(player);
                     self.state.board.set(game);
                     return ChessResponse::Ok;
@@ -84,223 +86,91 @@ impl Contract for ChessContract {
                 }
             }
 
+            // `CapturePiece` and `MakeMove` are now thin wrappers: the
+            // contract derives whether a move is a capture, en-passant, or
+            // castle from the board itself rather than trusting the
+            // client's classification.
             Operation::CapturePiece {
-                from,
-                to,
-                piece,
-                captured_piece,
-            } => {
-                // check if the game is still ongoing
-                self.is_game_over();
-
-                let block_time = self.runtime.system_time();
-                let clock = self.state.clock.get_mut();
-                let owner = self.runtime.authenticated_signer().unwrap();
-                let active_player = self.state.board.get().active;
-                let active = self
-                    .state
-                    .owners
-                    .get(&owner)
-                    .await
-                    .expect("Failed to get active player")
-                    .expect("Active player not found");
-                assert_eq!(
-                    active_player, active,
-                    "Only the active player can make a move."
-                );
-
-                if piece.starts_with("w")
-                    && active_player != Color::White
-                    && captured_piece.starts_with("w")
-                {
-                    return ChessResponse::Err(ChessError::InvalidCapture);
-                }
-                if piece.starts_with("b")
-                    && active_player != Color::Black
-                    && captured_piece.starts_with("b")
-                {
-                    return ChessResponse::Err(ChessError::InvalidCapture);
-                }
-
-                let piece = ChessBoard::get_piece(&piece).expect("Invalid piece");
-                let captured_piece = ChessBoard::get_piece(&captured_piece).expect("Invalid piece");
-                let from_sq = Square::from_str(&from).expect("Invalid square");
-                let to_sq = Square::from_str(&to).expect("Invalid square");
-                let m: MoveType = MoveType::Capture(captured_piece);
-
-                let success = self
-                    .state
-                    .board
-                    .get_mut()
-                    .make_move(from_sq, to_sq, piece, m);
-
-                match success {
-                    Ok(_) => {
-                        self.state.board.get_mut().switch_player_turn();
-                        let moves = ChessBoard::create_capture_string(&from, &to);
-                        self.state.board.get_mut().create_move_string(active, moves);
-
-                        self.runtime
-                            .assert_before(block_time.saturating_add(clock.block_delay));
-                        clock.make_move(block_time, active_player);
-
-                        self.state.board.get_mut().is_checkmate(); // check if the current player is checkmate, i.e if white makes a move after switch turn black is active player and we check if active player is in checkmate
-                        ChessResponse::Ok
-                    }
-                    Err(e) => return ChessResponse::Err(e),
-                }
-            }
+                from, to, piece, ..
+            } => self.validated_move(from, to, piece, None).await,
 
             Operation::MakeMove { from, to, piece } => {
-                // check if the game is still ongoing
-                self.is_game_over();
+                self.validated_move(from, to, piece, None).await
+            }
 
+            Operation::SetPosition { fen } => {
                 let owner = self.runtime.authenticated_signer().unwrap();
-                let active_player = self.state.board.get().active;
-                let active = self
-                    .state
-                    .owners
-                    .get(&owner)
-                    .await
-                    .expect("Failed to get active player")
-                    .expect("Active player not found");
-                assert_eq!(
-                    active_player, active,
-                    "Only the active player can make a move."
-                );
-
-                // Early return if the piece is not owned by the active player
-                if piece.starts_with("w") && active_player != Color::White {
-                    return ChessResponse::Err(ChessError::InvalidMove);
-                }
-
-                // Early return if the piece is not owned by the active player
-                if piece.starts_with("b") && active_player != Color::Black {
-                    return ChessResponse::Err(ChessError::InvalidMove);
-                }
-
-                let p = ChessBoard::get_piece(&piece).expect("Invalid piece");
-                let from_sq = Square::from_str(&from).expect("Invalid square");
-                let to_sq = Square::from_str(&to).expect("Invalid square");
-                let mut m: MoveType = MoveType::Move;
-
-                if self.state.board.get().board.en_passant & (1u64 << to_sq as usize) != 0
-                    && piece.ends_with("P")
-                {
-                    m = MoveType::EnPassant;
+                if self.state.owners.get(&owner).await.unwrap_or(None).is_none() {
+                    return ChessResponse::Err(ChessError::InvalidRequest);
                 }
-
-                match p {
-                    Piece::WhiteKing => {
-                        if from_sq == Square::E1 && to_sq == Square::G1 {
-                            m = MoveType::Castle(CastleType::KingSide);
-                        } else if from_sq == Square::E1 && to_sq == Square::C1 {
-                            m = MoveType::Castle(CastleType::QueenSide);
-                        }
-                    }
-                    Piece::BlackKing => {
-                        if from_sq == Square::E8 && to_sq == Square::G8 {
-                            m = MoveType::Castle(CastleType::KingSide);
-                        } else if from_sq == Square::E8 && to_sq == Square::C8 {
-                            m = MoveType::Castle(CastleType::QueenSide);
-                        }
-                    }
-                    _ => {}
+                // Only valid before the first move has been played.
+                if !self.state.board.get().moves.is_empty() {
+                    return ChessResponse::Err(ChessError::InvalidRequest);
                 }
 
-                let clock = self.state.clock.get_mut();
-                let block_time = self.runtime.system_time();
-
-                let success = self.state.board.get_mut().make_move(from_sq, to_sq, p, m);
-
-                match success {
-                    Ok(_) => {
-                        log::info!("Move successful");
-                        self.state.board.get_mut().switch_player_turn();
-                        self.state.board.get_mut().create_move_string(active, to);
-
-                        clock.make_move(block_time, active_player);
-                        self.runtime
-                            .assert_before(block_time.saturating_add(clock.block_delay));
-
-                        self.state.board.get_mut().is_checkmate();
+                match Game::with_fen(&fen) {
+                    Ok(game) => {
+                        self.state.board.set(game);
+                        // The position this offer referred to no longer
+                        // exists once the board is replaced wholesale.
+                        self.state.pending_takeback.set(None);
                         ChessResponse::Ok
                     }
-                    Err(e) => {
-                        log::info!("Move failed: {:?}", e);
-                        return ChessResponse::Err(ChessError::InvalidMove);
-                    }
+                    Err(e) => ChessResponse::Err(e),
                 }
             }
+
             Operation::PawnPromotion {
                 from,
                 to,
                 piece,
                 promoted_piece,
             } => {
-                // check if the game is still ongoing
-                self.is_game_over();
-
-                let from_sq = Square::from_str(&from).expect("Invalid square");
-                let piece = Piece::from_str(&piece).expect("Invalid piece");
+                self.validated_move(from, to, piece, Some(promoted_piece))
+                    .await
+            }
 
-                if piece != Piece::WhitePawn && piece != Piece::BlackPawn {
-                    return ChessResponse::Err(ChessError::InvalidPromotion);
+            Operation::OfferTakeback => {
+                let owner = self.runtime.authenticated_signer().unwrap();
+                if self.state.owners.get(&owner).await.unwrap_or(None).is_none() {
+                    return ChessResponse::Err(ChessError::InvalidRequest);
+                }
+                if self.state.board.get().history.is_empty() {
+                    return ChessResponse::Err(ChessError::InvalidRequest);
                 }
+                self.state.pending_takeback.set(Some(owner));
+                ChessResponse::Ok
+            }
 
-                if piece == Piece::WhitePawn {
-                    if from_sq.rank() != 7 {
-                        return ChessResponse::Err(ChessError::InvalidPromotion);
-                    }
-                } else if piece == Piece::BlackPawn {
-                    if from_sq.rank() != 2 {
-                        return ChessResponse::Err(ChessError::InvalidPromotion);
-                    }
+            Operation::AcceptTakeback => {
+                let owner = self.runtime.authenticated_signer().unwrap();
+                let offerer = match self.state.pending_takeback.get() {
+                    Some(offerer) => *offerer,
+                    None => return ChessResponse::Err(ChessError::InvalidRequest),
+                };
+                if owner == offerer {
+                    return ChessResponse::Err(ChessError::InvalidRequest);
                 }
 
-                let block_time = self.runtime.system_time();
+                let last_entry = self.state.board.get().history.last();
+                let resulting_zobrist_hash =
+                    last_entry.and_then(|entry| entry.resulting_zobrist_hash);
+                let previous_remaining = last_entry.map(|entry| entry.previous_remaining);
 
-                let clock = self.state.clock.get_mut();
-                let owner = self.runtime.authenticated_signer().unwrap();
-                let active_player = self.state.board.get().active;
-                let active = self
-                    .state
-                    .owners
-                    .get(&owner)
-                    .await
-                    .expect("Failed to get active player")
-                    .expect("Active player not found");
-                assert_eq!(
-                    active_player, active,
-                    "Only the active player can make a move."
-                );
-
-                let to_sq = Square::from_str(&to).expect("Invalid square");
-                let promoting_to = Piece::from_str(&promoted_piece).expect("Invalid piece");
-
-                let success = self.state.board.get_mut().make_move(
-                    from_sq,
-                    to_sq,
-                    piece,
-                    MoveType::Promotion(promoting_to),
-                );
-
-                match success {
-                    Ok(_) => {
-                        self.state.board.get_mut().switch_player_turn();
-                        self.state.board.get_mut().create_move_string(active, to);
-
-                        clock.make_move(block_time, active_player);
-                        self.runtime
-                            .assert_before(block_time.saturating_add(clock.block_delay));
-
-                        self.state.board.get_mut().is_checkmate();
-                        clock.make_move(block_time, active_player);
-                        self.runtime
-                            .assert_before(block_time.saturating_add(clock.block_delay));
+                match self.state.board.get_mut().undo_last_move() {
+                    Ok(previous_last_move) => {
+                        if let Some(hash) = resulting_zobrist_hash {
+                            self.rollback_repetition(hash).await;
+                        }
+                        let clock = self.state.clock.get_mut();
+                        clock.last_move = previous_last_move;
+                        if let Some(remaining) = previous_remaining {
+                            clock.remaining = remaining;
+                        }
+                        self.state.pending_takeback.set(None);
                         ChessResponse::Ok
                     }
-                    Err(e) => return ChessResponse::Err(e),
+                    Err(e) => ChessResponse::Err(e),
                 }
             }
         }
@@ -322,9 +192,201 @@ impl ChessContract {
             GameState::Stalemate => {
                 return ChessResponse::Err(ChessError::InvalidRequest);
             }
+            GameState::DrawByFiftyMove => {
+                return ChessResponse::Err(ChessError::InvalidRequest);
+            }
+            GameState::DrawByRepetition => {
+                return ChessResponse::Err(ChessError::InvalidRequest);
+            }
+            GameState::TimeOut { .. } => {
+                return ChessResponse::Err(ChessError::InvalidRequest);
+            }
             GameState::InPlay => {
                 return ChessResponse::Ok;
             }
         }
     }
+
+    /// Validates a submitted move against the server-side legal-move
+    /// generator, derives its `MoveType` from the board itself (capture,
+    /// en-passant, castle, promotion), applies it, and runs the shared
+    /// post-move bookkeeping (turn switch, move string, clock, draw
+    /// detection, checkmate). `CapturePiece`, `MakeMove`, and
+    /// `PawnPromotion` are all thin wrappers over this.
+    async fn validated_move(
+        &mut self,
+        from: String,
+        to: String,
+        piece: String,
+        promoted_piece: Option<String>,
+    ) -> ChessResponse {
+        if let ChessResponse::Err(e) = self.is_game_over() {
+            return ChessResponse::Err(e);
+        }
+
+        let active_player = self.state.board.get().active;
+        let block_time = self.runtime.system_time();
+        if self.state.clock.get().has_flagged(block_time, active_player) {
+            self.state.board.get_mut().state = GameState::TimeOut {
+                loser: active_player,
+            };
+            return ChessResponse::Err(ChessError::InvalidRequest);
+        }
+
+        let owner = self.runtime.authenticated_signer().unwrap();
+        let active = self
+            .state
+            .owners
+            .get(&owner)
+            .await
+            .expect("Failed to get active player")
+            .expect("Active player not found");
+        assert_eq!(
+            active_player, active,
+            "Only the active player can make a move."
+        );
+
+        let piece = match ChessBoard::get_piece(&piece) {
+            Some(p) => p,
+            None => return ChessResponse::Err(ChessError::InvalidPiece(piece)),
+        };
+        if piece.color() != active_player {
+            return ChessResponse::Err(ChessError::InvalidMove);
+        }
+
+        let from_sq = match Square::from_str(&from) {
+            Ok(s) => s,
+            Err(e) => return ChessResponse::Err(e),
+        };
+        let to_sq = match Square::from_str(&to) {
+            Ok(s) => s,
+            Err(e) => return ChessResponse::Err(e),
+        };
+        let promoted_piece = match promoted_piece {
+            Some(code) => match ChessBoard::get_piece(&code) {
+                Some(p) => Some(p),
+                None => return ChessResponse::Err(ChessError::InvalidPiece(code)),
+            },
+            None => None,
+        };
+
+        let board = &self.state.board.get().board;
+        if !board.is_legal_move(from_sq, to_sq, active_player) {
+            return ChessResponse::Err(ChessError::InvalidMove);
+        }
+        let move_type = match board.classify_move(from_sq, to_sq, piece, promoted_piece) {
+            Ok(move_type) => move_type,
+            Err(e) => return ChessResponse::Err(e),
+        };
+        // `classify_move` reports every pawn-to-back-rank move as
+        // `Promotion`, even ones that capture on the destination square, so
+        // whether this was a capture has to be read off the board rather
+        // than inferred from `move_type` alone.
+        let is_capture = board.piece_at(to_sq).is_some() || move_type == MoveType::EnPassant;
+
+        let clock_last_move = self.state.clock.get().last_move;
+        let previous_remaining = self.state.clock.get().remaining;
+        let success = self.state.board.get_mut().make_move(
+            from_sq,
+            to_sq,
+            piece,
+            move_type,
+            clock_last_move,
+            previous_remaining,
+        );
+
+        match success {
+            Ok(_) => {
+                self.state.board.get_mut().switch_player_turn();
+                let move_string = if is_capture {
+                    ChessBoard::create_capture_string(&from, &to)
+                } else {
+                    to.clone()
+                };
+                self.state
+                    .board
+                    .get_mut()
+                    .create_move_string(active, move_string);
+
+                let clock = self.state.clock.get_mut();
+                clock.make_move(block_time, active_player);
+                self.runtime
+                    .assert_before(block_time.saturating_add(clock.block_delay));
+
+                // A pending offer referred to the move that was on top of
+                // the history stack when it was made; once a new move has
+                // been recorded it no longer refers to anything sensible.
+                self.state.pending_takeback.set(None);
+
+                // Checkmate/stalemate must be decided before the fifty-move
+                // and repetition checks: both of those only flag a draw
+                // while the game is still `InPlay`, so if they ran first on
+                // a mating move they would stamp the game as drawn and
+                // `is_checkmate` would then short-circuit on the
+                // already-terminal state instead of reporting the mate.
+                self.state.board.get_mut().is_checkmate();
+                self.record_draw_conditions(piece, move_type).await;
+                ChessResponse::Ok
+            }
+            Err(e) => ChessResponse::Err(e),
+        }
+    }
+
+    /// Resets the halfmove clock on a pawn move or capture, records the
+    /// Zobrist hash of the resulting position, and flags a draw if the
+    /// fifty-move or threefold-repetition rule has been hit.
+    async fn record_draw_conditions(&mut self, piece: Piece, move_type: MoveType) {
+        let reset = piece.is_pawn()
+            || matches!(
+                move_type,
+                MoveType::Capture(_) | MoveType::EnPassant | MoveType::Promotion(_)
+            );
+        self.state.board.get_mut().advance_half_move_clock(reset);
+        self.state.board.get_mut().check_fifty_move_draw();
+
+        let hash = self.state.board.get().zobrist_hash();
+        let count = self
+            .state
+            .repetitions
+            .get(&hash)
+            .await
+            .expect("Failed to read repetition count")
+            .unwrap_or(0)
+            + 1;
+        self.state
+            .repetitions
+            .insert(&hash, count)
+            .expect("Failed to store repetition count");
+        if let Some(entry) = self.state.board.get_mut().history.last_mut() {
+            entry.resulting_zobrist_hash = Some(hash);
+        }
+
+        if count >= 3 && self.state.board.get().state == GameState::InPlay {
+            self.state.board.get_mut().state = GameState::DrawByRepetition;
+        }
+    }
+
+    /// Decrements (or removes) the repetition count recorded for `hash`,
+    /// undoing the bookkeeping `record_draw_conditions` did for the ply
+    /// that a takeback just reversed.
+    async fn rollback_repetition(&mut self, hash: u64) {
+        let count = self
+            .state
+            .repetitions
+            .get(&hash)
+            .await
+            .expect("Failed to read repetition count")
+            .unwrap_or(0);
+        if count <= 1 {
+            self.state
+                .repetitions
+                .remove(&hash)
+                .expect("Failed to remove repetition count");
+        } else {
+            self.state
+                .repetitions
+                .insert(&hash, count - 1)
+                .expect("Failed to store repetition count");
+        }
+    }
 }
@@ -0,0 +1,584 @@
+use std::str::FromStr;
+
+use linera_sdk::base::{TimeDelta, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::chessboard::ChessBoard;
+use crate::piece::{Color, Piece};
+use crate::square::Square;
+use crate::{CastleType, ChessError, MoveType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameState {
+    InPlay,
+    Checkmate,
+    Stalemate,
+    DrawByFiftyMove,
+    DrawByRepetition,
+    /// `loser`'s clock ran out before they submitted a move.
+    TimeOut { loser: Color },
+}
+
+/// The non-reversible state of a single ply: everything `undo_last_move`
+/// needs that cannot be derived by just moving the piece back. Piece
+/// positions themselves are reversible (remove from `to`, place on
+/// `from`), so only this small struct is persisted per move.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoEntry {
+    pub from: Square,
+    pub to: Square,
+    pub piece: Piece,
+    pub move_type: MoveType,
+    pub captured_piece: Option<Piece>,
+    pub previous_castling_rights: u8,
+    pub previous_en_passant: u64,
+    pub previous_half_move_clock: u32,
+    pub previous_clock_last_move: Timestamp,
+    /// `Clock::remaining` before this move's time was charged (and any
+    /// Fischer increment credited), so a takeback can restore it exactly.
+    pub previous_remaining: [TimeDelta; 2],
+    /// The Zobrist hash `record_draw_conditions` recorded a repetition
+    /// count for after this ply was played, if the move has been through
+    /// that bookkeeping. `AcceptTakeback` uses this to roll the count back.
+    pub resulting_zobrist_hash: Option<u64>,
+}
+
+/// A game in progress: the position plus everything needed to serialize it
+/// back out as FEN or PGN-ish move text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Game {
+    pub board: ChessBoard,
+    pub active: Color,
+    pub state: GameState,
+    pub moves: Vec<String>,
+    pub half_move_clock: u32,
+    pub full_move_number: u32,
+    /// Non-reversible per-ply state, used for takebacks and also the
+    /// backing store for repetition/PGN-style history.
+    pub history: Vec<UndoEntry>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
+impl Game {
+    pub fn new() -> Game {
+        Game {
+            board: ChessBoard::starting_position(),
+            active: Color::White,
+            state: GameState::InPlay,
+            moves: Vec::new(),
+            half_move_clock: 0,
+            full_move_number: 1,
+            history: Vec::new(),
+        }
+    }
+
+    /// Parses a full FEN record (piece placement, side to move, castling
+    /// rights, en-passant target, halfmove clock, fullmove number).
+    pub fn with_fen(fen: &str) -> Result<Game, ChessError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 {
+            return Err(ChessError::InvalidFen(format!(
+                "expected 6 space-separated fields, got {}",
+                fields.len()
+            )));
+        }
+        let [placement, active, castling, en_passant, half_move, full_move] = [
+            fields[0], fields[1], fields[2], fields[3], fields[4], fields[5],
+        ];
+
+        let mut board = ChessBoard::from_fen_placement(placement)?;
+
+        let active = match active {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(ChessError::InvalidFen(format!("bad side to move '{other}'"))),
+        };
+
+        board.castling_rights = 0;
+        if castling != "-" {
+            for c in castling.chars() {
+                match c {
+                    'K' => board.castling_rights |= 1 << 0,
+                    'Q' => board.castling_rights |= 1 << 1,
+                    'k' => board.castling_rights |= 1 << 2,
+                    'q' => board.castling_rights |= 1 << 3,
+                    other => {
+                        return Err(ChessError::InvalidFen(format!(
+                            "bad castling right '{other}'"
+                        )))
+                    }
+                }
+            }
+        }
+
+        board.en_passant = if en_passant == "-" {
+            0
+        } else {
+            let square = Square::from_str(en_passant)
+                .map_err(|_| ChessError::InvalidFen(format!("bad en-passant square '{en_passant}'")))?;
+            1u64 << square.index()
+        };
+
+        let half_move_clock: u32 = half_move
+            .parse()
+            .map_err(|_| ChessError::InvalidFen(format!("bad halfmove clock '{half_move}'")))?;
+        let full_move_number: u32 = full_move
+            .parse()
+            .map_err(|_| ChessError::InvalidFen(format!("bad fullmove number '{full_move}'")))?;
+        if full_move_number == 0 {
+            return Err(ChessError::InvalidFen(
+                "fullmove number must be at least 1".to_string(),
+            ));
+        }
+
+        board.validate(active)?;
+
+        Ok(Game {
+            board,
+            active,
+            state: GameState::InPlay,
+            moves: Vec::new(),
+            half_move_clock,
+            full_move_number,
+            history: Vec::new(),
+        })
+    }
+
+    /// Serializes the live position back out as a full FEN record.
+    pub fn to_fen(&self) -> String {
+        let mut castling = String::new();
+        if self.board.has_castling_right(Color::White, CastleType::KingSide) {
+            castling.push('K');
+        }
+        if self.board.has_castling_right(Color::White, CastleType::QueenSide) {
+            castling.push('Q');
+        }
+        if self.board.has_castling_right(Color::Black, CastleType::KingSide) {
+            castling.push('k');
+        }
+        if self.board.has_castling_right(Color::Black, CastleType::QueenSide) {
+            castling.push('q');
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = if self.board.en_passant == 0 {
+            "-".to_string()
+        } else {
+            Square::from_index(self.board.en_passant.trailing_zeros() as u8)
+                .expect("valid en-passant bit")
+                .to_string()
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            self.board.to_fen_placement(),
+            if self.active == Color::White { "w" } else { "b" },
+            castling,
+            en_passant,
+            self.half_move_clock,
+            self.full_move_number,
+        )
+    }
+
+    /// Applies a move to the live position and records the non-reversible
+    /// state needed to undo it later. Does not flip the active color or
+    /// touch game-over/draw state; callers drive those separately so they
+    /// can run bookkeeping (move strings, clocks) in between.
+    pub fn make_move(
+        &mut self,
+        from: Square,
+        to: Square,
+        piece: Piece,
+        move_type: MoveType,
+        clock_last_move: Timestamp,
+        previous_remaining: [TimeDelta; 2],
+    ) -> Result<(), ChessError> {
+        // `classify_move` reports every pawn-to-back-rank move as
+        // `Promotion`, including ones that land on an occupied square, so
+        // the captured piece for those has to come from the board rather
+        // than from the `MoveType` itself. `Move`/`Castle` never capture.
+        let captured_piece = match move_type {
+            MoveType::Move | MoveType::Castle(_) => None,
+            MoveType::EnPassant => Some(match piece.color() {
+                Color::White => Piece::BlackPawn,
+                Color::Black => Piece::WhitePawn,
+            }),
+            MoveType::Capture(_) | MoveType::Promotion(_) => self.board.piece_at(to),
+        };
+        let entry = UndoEntry {
+            from,
+            to,
+            piece,
+            move_type,
+            captured_piece,
+            previous_castling_rights: self.board.castling_rights,
+            previous_en_passant: self.board.en_passant,
+            previous_half_move_clock: self.half_move_clock,
+            previous_clock_last_move: clock_last_move,
+            previous_remaining,
+            resulting_zobrist_hash: None,
+        };
+
+        self.board.apply_move(from, to, piece, move_type)?;
+        self.history.push(entry);
+        Ok(())
+    }
+
+    /// Pops the last ply off the history and reverses it: moves the piece
+    /// back, restores the captured piece (if any), restores castling
+    /// rights/en-passant/halfmove clock, and flips the active color back
+    /// to the player who made that move. Returns the clock timestamp the
+    /// caller should restore onto `Clock::last_move`.
+    pub fn undo_last_move(&mut self) -> Result<Timestamp, ChessError> {
+        let entry = self.history.pop().ok_or(ChessError::InvalidRequest)?;
+
+        match entry.move_type {
+            MoveType::Castle(side) => {
+                self.board.remove_piece(entry.to, entry.piece);
+                self.board.place_piece(entry.from, entry.piece);
+                let rank = entry.from.rank();
+                let (rook_from, rook_to) = match side {
+                    CastleType::KingSide => (
+                        Square::from_file_rank(8, rank).expect("in range"),
+                        Square::from_file_rank(6, rank).expect("in range"),
+                    ),
+                    CastleType::QueenSide => (
+                        Square::from_file_rank(1, rank).expect("in range"),
+                        Square::from_file_rank(4, rank).expect("in range"),
+                    ),
+                };
+                let rook = match entry.piece.color() {
+                    Color::White => Piece::WhiteRook,
+                    Color::Black => Piece::BlackRook,
+                };
+                self.board.remove_piece(rook_to, rook);
+                self.board.place_piece(rook_from, rook);
+            }
+            MoveType::EnPassant => {
+                self.board.remove_piece(entry.to, entry.piece);
+                self.board.place_piece(entry.from, entry.piece);
+                let captured_square = Square::from_index(match entry.piece.color() {
+                    Color::White => entry.to.index() - 8,
+                    Color::Black => entry.to.index() + 8,
+                })
+                .expect("valid square");
+                if let Some(captured) = entry.captured_piece {
+                    self.board.place_piece(captured_square, captured);
+                }
+            }
+            MoveType::Promotion(promoted) => {
+                self.board.remove_piece(entry.to, promoted);
+                self.board.place_piece(entry.from, entry.piece);
+                if let Some(captured) = entry.captured_piece {
+                    self.board.place_piece(entry.to, captured);
+                }
+            }
+            MoveType::Move | MoveType::Capture(_) => {
+                self.board.remove_piece(entry.to, entry.piece);
+                self.board.place_piece(entry.from, entry.piece);
+                if let Some(captured) = entry.captured_piece {
+                    self.board.place_piece(entry.to, captured);
+                }
+            }
+        }
+
+        self.board.castling_rights = entry.previous_castling_rights;
+        self.board.en_passant = entry.previous_en_passant;
+        self.half_move_clock = entry.previous_half_move_clock;
+
+        // The mover is whoever is not currently active, since `active` was
+        // already switched to the opponent when this move was made.
+        let mover = self.active.opponent();
+        if mover == Color::Black {
+            self.full_move_number -= 1;
+        }
+        self.active = mover;
+        self.state = GameState::InPlay;
+
+        match mover {
+            Color::White => {
+                self.moves.pop();
+            }
+            Color::Black => {
+                if let Some(last) = self.moves.last_mut() {
+                    if let Some(space) = last.rfind(' ') {
+                        last.truncate(space);
+                    }
+                }
+            }
+        }
+
+        Ok(entry.previous_clock_last_move)
+    }
+
+    pub fn switch_player_turn(&mut self) {
+        if self.active == Color::Black {
+            self.full_move_number += 1;
+        }
+        self.active = self.active.opponent();
+    }
+
+    /// Appends `mv` to the move list, pairing white's move with the move
+    /// number and black's move onto the same entry (e.g. `"1. e4 e5"`).
+    pub fn create_move_string(&mut self, color: Color, mv: String) {
+        match color {
+            Color::White => self.moves.push(format!("{}. {}", self.full_move_number, mv)),
+            Color::Black => match self.moves.last_mut() {
+                Some(last) => {
+                    last.push(' ');
+                    last.push_str(&mv);
+                }
+                None => self.moves.push(mv),
+            },
+        }
+    }
+
+    /// Advances the halfmove clock, resetting it on any pawn move or
+    /// capture (the only events that make a position irreversible).
+    pub fn advance_half_move_clock(&mut self, reset: bool) {
+        self.half_move_clock = if reset { 0 } else { self.half_move_clock + 1 };
+    }
+
+    /// Declares a draw once the halfmove clock reaches 100 (50 full moves
+    /// without a pawn move or capture).
+    pub fn check_fifty_move_draw(&mut self) {
+        if self.state == GameState::InPlay && self.half_move_clock >= 100 {
+            self.state = GameState::DrawByFiftyMove;
+        }
+    }
+
+    /// Zobrist hash of the live position, used to detect threefold
+    /// repetition. Only positions with identical castling and en-passant
+    /// rights hash equal, which is exactly the rule's requirement.
+    pub fn zobrist_hash(&self) -> u64 {
+        self.board.zobrist_hash(self.active)
+    }
+
+    /// Flags checkmate or stalemate for the side to move, based on
+    /// `ChessBoard::has_any_legal_move`.
+    pub fn is_checkmate(&mut self) -> bool {
+        if self.state != GameState::InPlay {
+            return self.state == GameState::Checkmate;
+        }
+        if self.board.has_any_legal_move(self.active) {
+            return false;
+        }
+        self.state = if self.board.is_in_check(self.active) {
+            GameState::Checkmate
+        } else {
+            GameState::Stalemate
+        };
+        self.state == GameState::Checkmate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_elapsed_time() -> (Timestamp, [TimeDelta; 2]) {
+        (Timestamp::default(), [TimeDelta::default(), TimeDelta::default()])
+    }
+
+    #[test]
+    fn make_move_then_undo_restores_starting_position() {
+        let mut game = Game::new();
+        let (clock_last_move, previous_remaining) = no_elapsed_time();
+
+        game.make_move(
+            Square::E2,
+            Square::E4,
+            Piece::WhitePawn,
+            MoveType::Move,
+            clock_last_move,
+            previous_remaining,
+        )
+        .unwrap();
+        game.switch_player_turn();
+
+        let restored_last_move = game.undo_last_move().unwrap();
+
+        assert_eq!(restored_last_move, clock_last_move);
+        assert_eq!(game.board, ChessBoard::starting_position());
+        assert_eq!(game.active, Color::White);
+        assert!(game.history.is_empty());
+    }
+
+    #[test]
+    fn undo_restores_piece_captured_by_a_promotion() {
+        let mut game = Game::new();
+        game.board = ChessBoard {
+            pieces: [0u64; 12],
+            en_passant: 0,
+            castling_rights: 0,
+        };
+        game.board.place_piece(Square::E1, Piece::WhiteKing);
+        game.board.place_piece(Square::E8, Piece::BlackKing);
+        game.board.place_piece(Square::A7, Piece::WhitePawn);
+        game.board.place_piece(Square::B8, Piece::BlackRook);
+
+        let move_type = game
+            .board
+            .classify_move(
+                Square::A7,
+                Square::B8,
+                Piece::WhitePawn,
+                Some(Piece::WhiteQueen),
+            )
+            .unwrap();
+        let (clock_last_move, previous_remaining) = no_elapsed_time();
+
+        game.make_move(
+            Square::A7,
+            Square::B8,
+            Piece::WhitePawn,
+            move_type,
+            clock_last_move,
+            previous_remaining,
+        )
+        .unwrap();
+        game.switch_player_turn();
+        assert_eq!(game.board.piece_at(Square::B8), Some(Piece::WhiteQueen));
+
+        game.undo_last_move().unwrap();
+
+        assert_eq!(game.board.piece_at(Square::B8), Some(Piece::BlackRook));
+        assert_eq!(game.board.piece_at(Square::A7), Some(Piece::WhitePawn));
+        assert_eq!(game.active, Color::White);
+    }
+
+    #[test]
+    fn with_fen_then_to_fen_round_trips_the_starting_position() {
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let game = Game::with_fen(fen).unwrap();
+
+        assert_eq!(game.board, ChessBoard::starting_position());
+        assert_eq!(game.active, Color::White);
+        assert_eq!(game.half_move_clock, 0);
+        assert_eq!(game.full_move_number, 1);
+        assert_eq!(game.to_fen(), fen);
+    }
+
+    #[test]
+    fn with_fen_parses_partial_castling_rights_and_en_passant_square() {
+        let fen = "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 3";
+        let game = Game::with_fen(fen).unwrap();
+
+        assert!(game.board.has_castling_right(Color::White, CastleType::KingSide));
+        assert!(!game.board.has_castling_right(Color::White, CastleType::QueenSide));
+        assert!(!game.board.has_castling_right(Color::Black, CastleType::KingSide));
+        assert!(game.board.has_castling_right(Color::Black, CastleType::QueenSide));
+        assert_eq!(game.board.en_passant, 1u64 << Square::D6.index());
+        assert_eq!(game.to_fen(), fen);
+    }
+
+    #[test]
+    fn with_fen_rejects_wrong_field_count() {
+        let err = Game::with_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -").unwrap_err();
+        assert!(matches!(err, ChessError::InvalidFen(_)));
+    }
+
+    #[test]
+    fn with_fen_rejects_bad_side_to_move() {
+        let err =
+            Game::with_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1").unwrap_err();
+        assert!(matches!(err, ChessError::InvalidFen(_)));
+    }
+
+    #[test]
+    fn with_fen_rejects_bad_castling_char() {
+        let err =
+            Game::with_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkqx - 0 1").unwrap_err();
+        assert!(matches!(err, ChessError::InvalidFen(_)));
+    }
+
+    #[test]
+    fn with_fen_rejects_bad_en_passant_square() {
+        let err =
+            Game::with_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq z9 0 1").unwrap_err();
+        assert!(matches!(err, ChessError::InvalidFen(_)));
+    }
+
+    #[test]
+    fn with_fen_rejects_unparseable_move_counters() {
+        let err =
+            Game::with_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1").unwrap_err();
+        assert!(matches!(err, ChessError::InvalidFen(_)));
+
+        let err =
+            Game::with_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 x").unwrap_err();
+        assert!(matches!(err, ChessError::InvalidFen(_)));
+    }
+
+    #[test]
+    fn with_fen_rejects_zero_fullmove_number() {
+        let err =
+            Game::with_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0").unwrap_err();
+        assert!(matches!(err, ChessError::InvalidFen(_)));
+    }
+
+    #[test]
+    fn advance_half_move_clock_resets_on_pawn_move_or_capture() {
+        let mut game = Game::new();
+        game.half_move_clock = 7;
+
+        game.advance_half_move_clock(true);
+        assert_eq!(game.half_move_clock, 0);
+
+        game.advance_half_move_clock(false);
+        assert_eq!(game.half_move_clock, 1);
+    }
+
+    #[test]
+    fn check_fifty_move_draw_flags_at_one_hundred_halfmoves() {
+        let mut game = Game::new();
+        game.half_move_clock = 99;
+        game.check_fifty_move_draw();
+        assert_eq!(game.state, GameState::InPlay);
+
+        game.half_move_clock = 100;
+        game.check_fifty_move_draw();
+        assert_eq!(game.state, GameState::DrawByFiftyMove);
+    }
+
+    #[test]
+    fn check_fifty_move_draw_does_not_override_a_terminal_state() {
+        let mut game = Game::new();
+        game.half_move_clock = 100;
+        game.state = GameState::Checkmate;
+
+        game.check_fifty_move_draw();
+
+        assert_eq!(game.state, GameState::Checkmate);
+    }
+
+    #[test]
+    fn zobrist_hash_matches_for_identical_positions_and_differs_after_a_move() {
+        let game = Game::new();
+        let other = Game::new();
+        assert_eq!(game.zobrist_hash(), other.zobrist_hash());
+
+        let mut moved = Game::new();
+        let (clock_last_move, previous_remaining) = no_elapsed_time();
+        moved
+            .make_move(
+                Square::E2,
+                Square::E4,
+                Piece::WhitePawn,
+                MoveType::Move,
+                clock_last_move,
+                previous_remaining,
+            )
+            .unwrap();
+        moved.switch_player_turn();
+
+        assert_ne!(game.zobrist_hash(), moved.zobrist_hash());
+    }
+}
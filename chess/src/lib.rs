@@ -0,0 +1,123 @@
+pub mod chessboard;
+mod clock;
+mod game;
+pub mod piece;
+pub mod square;
+
+use async_graphql::{Request, Response};
+use linera_sdk::base::{ContractAbi, Owner, ServiceAbi, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+pub use clock::Clock;
+pub use game::{Game, GameState};
+use piece::Piece;
+
+pub struct ChessAbi;
+
+impl ContractAbi for ChessAbi {
+    type Operation = Operation;
+    type Response = ChessResponse;
+}
+
+impl ServiceAbi for ChessAbi {
+    type Query = Request;
+    type QueryResponse = Response;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstantiationArgument {
+    pub players: [Owner; 2],
+    pub block_delay: TimeDelta,
+    /// How much time each side starts the game with.
+    pub initial_time: TimeDelta,
+    /// Bonus time applied after each move, if any.
+    pub increment: TimeIncrement,
+}
+
+/// The bonus-time rule applied to a player's clock after they move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeIncrement {
+    /// The clock only ever counts down.
+    None,
+    /// Added to the mover's remaining time after each move.
+    Fischer(TimeDelta),
+    /// Time spent on a move within `delay` of the clock is free; only the
+    /// remainder is charged against the mover's remaining time.
+    Delay(TimeDelta),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CastleType {
+    KingSide,
+    QueenSide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MoveType {
+    Move,
+    Capture(Piece),
+    EnPassant,
+    Promotion(Piece),
+    Castle(CastleType),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Operation {
+    NewGame {
+        player: Owner,
+    },
+    MakeMove {
+        from: String,
+        to: String,
+        piece: String,
+    },
+    CapturePiece {
+        from: String,
+        to: String,
+        piece: String,
+        captured_piece: String,
+    },
+    PawnPromotion {
+        from: String,
+        to: String,
+        piece: String,
+        promoted_piece: String,
+    },
+    /// Sets the live position from a full FEN record. Only valid before the
+    /// first move of the game has been played.
+    SetPosition {
+        fen: String,
+    },
+    /// Requests that the last move be taken back. Requires the opponent to
+    /// confirm with `AcceptTakeback`.
+    OfferTakeback,
+    /// Accepts a pending takeback offer from the opponent, undoing the
+    /// last move played.
+    AcceptTakeback,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ChessResponse {
+    Ok,
+    Err(ChessError),
+}
+
+#[derive(Debug, Clone, thiserror::Error, Serialize, Deserialize)]
+pub enum ChessError {
+    #[error("request not allowed in the current game state")]
+    InvalidRequest,
+    #[error("invalid move")]
+    InvalidMove,
+    #[error("invalid capture")]
+    InvalidCapture,
+    #[error("invalid promotion")]
+    InvalidPromotion,
+    #[error("invalid square: {0}")]
+    InvalidSquare(String),
+    #[error("invalid piece: {0}")]
+    InvalidPiece(String),
+    #[error("invalid FEN record: {0}")]
+    InvalidFen(String),
+    #[error("invalid position: {0}")]
+    InvalidPosition(String),
+}
@@ -0,0 +1,152 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ChessError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub fn opponent(&self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+/// The piece set, keyed by color so that every bitboard in
+/// [`crate::chessboard::ChessBoard`] has a dedicated variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Piece {
+    WhitePawn,
+    WhiteKnight,
+    WhiteBishop,
+    WhiteRook,
+    WhiteQueen,
+    WhiteKing,
+    BlackPawn,
+    BlackKnight,
+    BlackBishop,
+    BlackRook,
+    BlackQueen,
+    BlackKing,
+}
+
+impl Piece {
+    pub fn color(&self) -> Color {
+        match self {
+            Piece::WhitePawn
+            | Piece::WhiteKnight
+            | Piece::WhiteBishop
+            | Piece::WhiteRook
+            | Piece::WhiteQueen
+            | Piece::WhiteKing => Color::White,
+            _ => Color::Black,
+        }
+    }
+
+    /// Single-letter FEN code, uppercase for white and lowercase for black.
+    pub fn fen_char(&self) -> char {
+        let c = match self {
+            Piece::WhitePawn | Piece::BlackPawn => 'P',
+            Piece::WhiteKnight | Piece::BlackKnight => 'N',
+            Piece::WhiteBishop | Piece::BlackBishop => 'B',
+            Piece::WhiteRook | Piece::BlackRook => 'R',
+            Piece::WhiteQueen | Piece::BlackQueen => 'Q',
+            Piece::WhiteKing | Piece::BlackKing => 'K',
+        };
+        if self.color() == Color::White {
+            c
+        } else {
+            c.to_ascii_lowercase()
+        }
+    }
+
+    pub fn from_fen_char(c: char) -> Option<Piece> {
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let piece = match c.to_ascii_uppercase() {
+            'P' => Piece::WhitePawn,
+            'N' => Piece::WhiteKnight,
+            'B' => Piece::WhiteBishop,
+            'R' => Piece::WhiteRook,
+            'Q' => Piece::WhiteQueen,
+            'K' => Piece::WhiteKing,
+            _ => return None,
+        };
+        Some(if color == Color::White {
+            piece
+        } else {
+            match piece {
+                Piece::WhitePawn => Piece::BlackPawn,
+                Piece::WhiteKnight => Piece::BlackKnight,
+                Piece::WhiteBishop => Piece::BlackBishop,
+                Piece::WhiteRook => Piece::BlackRook,
+                Piece::WhiteQueen => Piece::BlackQueen,
+                Piece::WhiteKing => Piece::BlackKing,
+                _ => unreachable!(),
+            }
+        })
+    }
+
+    pub fn is_pawn(&self) -> bool {
+        matches!(self, Piece::WhitePawn | Piece::BlackPawn)
+    }
+
+    pub fn is_king(&self) -> bool {
+        matches!(self, Piece::WhiteKing | Piece::BlackKing)
+    }
+
+    /// Index into the per-piece bitboard array kept by `ChessBoard`.
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub const ALL: [Piece; 12] = [
+        Piece::WhitePawn,
+        Piece::WhiteKnight,
+        Piece::WhiteBishop,
+        Piece::WhiteRook,
+        Piece::WhiteQueen,
+        Piece::WhiteKing,
+        Piece::BlackPawn,
+        Piece::BlackKnight,
+        Piece::BlackBishop,
+        Piece::BlackRook,
+        Piece::BlackQueen,
+        Piece::BlackKing,
+    ];
+}
+
+/// The client/contract-facing notation used throughout `contract.rs`, e.g.
+/// `"wP"` for a white pawn or `"bK"` for a black king.
+impl FromStr for Piece {
+    type Err = ChessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if bytes.len() != 2 {
+            return Err(ChessError::InvalidPiece(s.to_string()));
+        }
+        let color = match bytes[0] {
+            b'w' => Color::White,
+            b'b' => Color::Black,
+            _ => return Err(ChessError::InvalidPiece(s.to_string())),
+        };
+        let kind = bytes[1] as char;
+        let fen_char = if color == Color::White {
+            kind.to_ascii_uppercase()
+        } else {
+            kind.to_ascii_lowercase()
+        };
+        Piece::from_fen_char(fen_char).ok_or(ChessError::InvalidPiece(s.to_string()))
+    }
+}
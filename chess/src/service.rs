@@ -0,0 +1,89 @@
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_graphql::{EmptyMutation, EmptySubscription, Object, Request, Response, Schema};
+use chess::{square::Square, ChessAbi};
+use linera_sdk::{base::WithServiceAbi, views::View, Service, ServiceRuntime};
+
+use self::state::Chess;
+
+pub struct ChessService {
+    state: Arc<Chess>,
+}
+
+linera_sdk::service!(ChessService);
+
+impl WithServiceAbi for ChessService {
+    type Abi = ChessAbi;
+}
+
+impl Service for ChessService {
+    type Parameters = ();
+
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
+        let state = Chess::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        ChessService {
+            state: Arc::new(state),
+        }
+    }
+
+    async fn handle_query(&self, request: Request) -> Response {
+        let schema = Schema::build(
+            QueryRoot {
+                state: self.state.clone(),
+            },
+            EmptyMutation,
+            EmptySubscription,
+        )
+        .finish();
+        schema.execute(request).await
+    }
+}
+
+struct QueryRoot {
+    state: Arc<Chess>,
+}
+
+#[Object]
+impl QueryRoot {
+    /// The live position as a full FEN record.
+    async fn fen(&self) -> String {
+        self.state.board.get().to_fen()
+    }
+
+    /// Legal destination squares for the piece standing on `square`, empty
+    /// if the square is empty or it is not that piece's color's turn.
+    async fn legal_moves(&self, square: String) -> Vec<String> {
+        let Ok(square) = Square::from_str(&square) else {
+            return Vec::new();
+        };
+        let game = self.state.board.get();
+        let Some(piece) = game.board.piece_at(square) else {
+            return Vec::new();
+        };
+        if piece.color() != game.active {
+            return Vec::new();
+        }
+
+        let mut destinations: Vec<String> = game
+            .board
+            .legal_destinations(square, piece.color())
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect();
+        destinations.extend(
+            game.board
+                .legal_castle_moves(piece.color())
+                .into_iter()
+                .filter(|(from, _, _)| *from == square)
+                .map(|(_, to, _)| to.to_string()),
+        );
+        destinations
+    }
+}
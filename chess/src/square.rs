@@ -0,0 +1,87 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ChessError;
+
+/// A square on the board, indexed 0..=63 in little-endian rank-file order
+/// (`A1` = 0, `B1` = 1, ..., `H1` = 7, `A2` = 8, ..., `H8` = 63) so that
+/// `1u64 << square as usize` maps directly onto the bitboards in
+/// [`crate::chessboard::ChessBoard`].
+#[rustfmt::skip]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Square {
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+#[rustfmt::skip]
+const ALL: [Square; 64] = [
+    Square::A1, Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1, Square::H1,
+    Square::A2, Square::B2, Square::C2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2,
+    Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3,
+    Square::A4, Square::B4, Square::C4, Square::D4, Square::E4, Square::F4, Square::G4, Square::H4,
+    Square::A5, Square::B5, Square::C5, Square::D5, Square::E5, Square::F5, Square::G5, Square::H5,
+    Square::A6, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6,
+    Square::A7, Square::B7, Square::C7, Square::D7, Square::E7, Square::F7, Square::G7, Square::H7,
+    Square::A8, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8, Square::H8,
+];
+
+impl Square {
+    /// Builds a `Square` from a little-endian rank-file index (0..=63).
+    pub fn from_index(index: u8) -> Option<Square> {
+        ALL.get(index as usize).copied()
+    }
+
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    /// 1-based rank (1..=8).
+    pub fn rank(&self) -> u8 {
+        self.index() / 8 + 1
+    }
+
+    /// 1-based file (1..=8, a=1).
+    pub fn file(&self) -> u8 {
+        self.index() % 8 + 1
+    }
+
+    pub fn from_file_rank(file: u8, rank: u8) -> Option<Square> {
+        if !(1..=8).contains(&file) || !(1..=8).contains(&rank) {
+            return None;
+        }
+        Square::from_index((rank - 1) * 8 + (file - 1))
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = (b'a' + self.file() - 1) as char;
+        write!(f, "{}{}", file, self.rank())
+    }
+}
+
+impl FromStr for Square {
+    type Err = ChessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if bytes.len() != 2 {
+            return Err(ChessError::InvalidSquare(s.to_string()));
+        }
+        let file = bytes[0].to_ascii_lowercase();
+        let rank = bytes[1];
+        if !(b'a'..=b'h').contains(&file) || !(b'1'..=b'8').contains(&rank) {
+            return Err(ChessError::InvalidSquare(s.to_string()));
+        }
+        Square::from_file_rank(file - b'a' + 1, rank - b'0').ok_or(ChessError::InvalidSquare(s.to_string()))
+    }
+}
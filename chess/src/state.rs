@@ -0,0 +1,33 @@
+use chess::{piece::Color, Clock, Game};
+use linera_sdk::{
+    base::Owner,
+    views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext},
+};
+
+/// The persisted state of a single chess game instance.
+#[derive(RootView)]
+#[view(context = "ViewStorageContext")]
+pub struct Chess {
+    pub board: RegisterView<Game>,
+    pub clock: RegisterView<Clock>,
+    /// Maps each registered player to the color they are assigned once the
+    /// game starts.
+    pub owners: MapView<Owner, Color>,
+    /// Players waiting for (or seated at) the table, in join order.
+    pub players: RegisterView<Vec<Owner>>,
+    /// How many times each Zobrist hash has been reached, for threefold
+    /// repetition detection.
+    pub repetitions: MapView<u64, u32>,
+    /// The player who last offered a takeback, if any offer is outstanding.
+    pub pending_takeback: RegisterView<Option<Owner>>,
+}
+
+impl Chess {
+    pub fn get_players(&self) -> Vec<Owner> {
+        self.players.get().clone()
+    }
+
+    pub fn add_player(&mut self, player: Owner) {
+        self.players.get_mut().push(player);
+    }
+}